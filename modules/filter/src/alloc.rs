@@ -0,0 +1,96 @@
+//! WASM linear-memory allocation, backed by a side table that remembers each live
+//! allocation's true capacity so `dealloc`/`realloc` stay sound even if the host forgets or
+//! misreports the original size.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Pointer address -> (requested len, actual capacity) for every live allocation.
+/// WASM is single-threaded, but a `Mutex` keeps this sound without relying on that.
+fn registry() -> &'static Mutex<HashMap<usize, (usize, usize)>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<usize, (usize, usize)>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Allocate `size` bytes in WASM linear memory and return the base pointer.
+///
+/// # Safety
+///
+/// This function is unsafe because it directly interacts with WASM linear memory.
+#[unsafe(no_mangle)]
+pub extern "C" fn alloc(size: usize) -> *mut u8 {
+    let mut buf = Vec::<u8>::with_capacity(size);
+    let ptr = buf.as_mut_ptr();
+    let cap = buf.capacity();
+    std::mem::forget(buf);
+
+    registry().lock().unwrap().insert(ptr as usize, (size, cap));
+    ptr
+}
+
+/// Grow or shrink a previously-`alloc`'d buffer, copying its contents if the allocator can't
+/// resize in place. `old_size` is only consulted when `ptr` is unknown to the registry (e.g.
+/// it was never returned by `alloc`/`realloc`).
+///
+/// Exported as `filter_realloc` rather than `realloc`: the WASI libc this module links
+/// against already defines a C `realloc`, and a same-named export with a different (three
+/// rather than two param) ABI would silently shadow it.
+///
+/// # Safety
+///
+/// `ptr` must either be null or have been returned by a prior call to `alloc`/`realloc` that
+/// has not since been freed.
+#[unsafe(export_name = "filter_realloc")]
+pub unsafe extern "C" fn realloc(ptr: *mut u8, old_size: usize, new_size: usize) -> *mut u8 {
+    if ptr.is_null() {
+        return alloc(new_size);
+    }
+
+    let (len, cap) = registry()
+        .lock()
+        .unwrap()
+        .remove(&(ptr as usize))
+        .unwrap_or((old_size, old_size));
+
+    // Safety: `(len, cap)` came from the registry entry this pointer was allocated with, or
+    // (for a pointer the registry doesn't know about) we trust the caller-supplied `old_size`.
+    let mut buf = unsafe { Vec::from_raw_parts(ptr, len.min(cap), cap) };
+    buf.resize(new_size, 0);
+    buf.shrink_to_fit();
+
+    let new_ptr = buf.as_mut_ptr();
+    let new_cap = buf.capacity();
+    std::mem::forget(buf);
+
+    registry()
+        .lock()
+        .unwrap()
+        .insert(new_ptr as usize, (new_size, new_cap));
+    new_ptr
+}
+
+/// Free a previously allocated buffer. The true capacity is looked up in the registry, so a
+/// `size` that no longer matches what `alloc`/`realloc` actually reserved can't corrupt the
+/// heap the way reconstructing `Vec::from_raw_parts` straight from the caller's `size` could.
+///
+/// # Safety
+///
+/// `ptr` must either be null or have been returned by a prior call to `alloc`/`realloc` that
+/// has not since been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, size: usize) {
+    if ptr.is_null() {
+        return;
+    }
+
+    let cap = registry()
+        .lock()
+        .unwrap()
+        .remove(&(ptr as usize))
+        .map(|(_, cap)| cap)
+        .unwrap_or(size);
+
+    unsafe {
+        let _ = Vec::from_raw_parts(ptr, 0, cap);
+    }
+}
@@ -0,0 +1,143 @@
+//! Per-pixel colorspace conversions over a decoded image, producing channel-separated bytes
+//! in the requested layout rather than a re-encoded container format. `image` has no encoder
+//! for YCbCr/HSL/HSV/CMYK, so these feed analysis pipelines directly instead of round-tripping
+//! through a file format.
+
+use image::{DynamicImage, RgbaImage};
+
+use crate::format::ColorType;
+
+/// Colorspace a caller can request `to_colorspace` convert into.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorspaceTarget {
+    Gray = 0,
+    Rgb = 1,
+    Rgba = 2,
+    YCbCr = 3,
+    Hsl = 4,
+    Hsv = 5,
+    Cmyk = 6,
+}
+
+impl ColorspaceTarget {
+    /// Decode a raw tag byte received over FFI. Returns `None` for unrecognized tags.
+    pub fn from_u8(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Self::Gray),
+            1 => Some(Self::Rgb),
+            2 => Some(Self::Rgba),
+            3 => Some(Self::YCbCr),
+            4 => Some(Self::Hsl),
+            5 => Some(Self::Hsv),
+            6 => Some(Self::Cmyk),
+            _ => None,
+        }
+    }
+}
+
+/// Convert `img` into `target`'s channel layout, returning the raw bytes alongside the
+/// `ColorType` that describes them.
+pub fn convert(img: &DynamicImage, target: ColorspaceTarget) -> (Vec<u8>, ColorType) {
+    match target {
+        ColorspaceTarget::Gray => (img.to_luma8().into_raw(), ColorType::L8),
+        ColorspaceTarget::Rgb => (img.to_rgb8().into_raw(), ColorType::Rgb8),
+        ColorspaceTarget::Rgba => (img.to_rgba8().into_raw(), ColorType::Rgba8),
+        ColorspaceTarget::YCbCr => (to_ycbcr(&img.to_rgba8()), ColorType::YCbCr8),
+        ColorspaceTarget::Hsl => (to_hsl(&img.to_rgba8()), ColorType::Hsl8),
+        ColorspaceTarget::Hsv => (to_hsv(&img.to_rgba8()), ColorType::Hsv8),
+        ColorspaceTarget::Cmyk => (to_cmyk(&img.to_rgba8()), ColorType::Cmyk8),
+    }
+}
+
+fn to_ycbcr(rgba: &RgbaImage) -> Vec<u8> {
+    let mut out = Vec::with_capacity(rgba.pixels().len() * 3);
+    for px in rgba.pixels() {
+        let [r, g, b, _] = px.0.map(f32::from);
+        let y = 0.299 * r + 0.587 * g + 0.114 * b;
+        let cb = 128.0 - 0.168736 * r - 0.331264 * g + 0.5 * b;
+        let cr = 128.0 + 0.5 * r - 0.418688 * g - 0.081312 * b;
+        out.push(clamp_u8(y));
+        out.push(clamp_u8(cb));
+        out.push(clamp_u8(cr));
+    }
+    out
+}
+
+fn to_hsl(rgba: &RgbaImage) -> Vec<u8> {
+    let mut out = Vec::with_capacity(rgba.pixels().len() * 3);
+    for px in rgba.pixels() {
+        let [r, g, b, _] = px.0.map(|c| f32::from(c) / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let lightness = (max + min) / 2.0;
+        let saturation = if chroma == 0.0 {
+            0.0
+        } else {
+            chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+
+        out.push(clamp_u8(hue_degrees(r, g, b, max, chroma) / 360.0 * 255.0));
+        out.push(clamp_u8(saturation * 255.0));
+        out.push(clamp_u8(lightness * 255.0));
+    }
+    out
+}
+
+fn to_hsv(rgba: &RgbaImage) -> Vec<u8> {
+    let mut out = Vec::with_capacity(rgba.pixels().len() * 3);
+    for px in rgba.pixels() {
+        let [r, g, b, _] = px.0.map(|c| f32::from(c) / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+
+        out.push(clamp_u8(hue_degrees(r, g, b, max, chroma) / 360.0 * 255.0));
+        out.push(clamp_u8(saturation * 255.0));
+        out.push(clamp_u8(max * 255.0));
+    }
+    out
+}
+
+fn to_cmyk(rgba: &RgbaImage) -> Vec<u8> {
+    let mut out = Vec::with_capacity(rgba.pixels().len() * 4);
+    for px in rgba.pixels() {
+        let [r, g, b, _] = px.0.map(|c| f32::from(c) / 255.0);
+        let k = 1.0 - r.max(g).max(b);
+        let (c, m, y) = if k >= 1.0 {
+            (0.0, 0.0, 0.0)
+        } else {
+            (
+                (1.0 - r - k) / (1.0 - k),
+                (1.0 - g - k) / (1.0 - k),
+                (1.0 - b - k) / (1.0 - k),
+            )
+        };
+        out.push(clamp_u8(c * 255.0));
+        out.push(clamp_u8(m * 255.0));
+        out.push(clamp_u8(y * 255.0));
+        out.push(clamp_u8(k * 255.0));
+    }
+    out
+}
+
+/// Shared hue computation (in degrees) for the HSL/HSV max/min/chroma math.
+fn hue_degrees(r: f32, g: f32, b: f32, max: f32, chroma: f32) -> f32 {
+    if chroma == 0.0 {
+        return 0.0;
+    }
+    let sector = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    sector * 60.0
+}
+
+fn clamp_u8(v: f32) -> u8 {
+    v.round().clamp(0.0, 255.0) as u8
+}
@@ -0,0 +1,40 @@
+//! Structured FFI return types: a typed status code and decoded-image metadata, so the
+//! boundary stops overloading a `0`-length result to mean "something failed".
+
+use crate::format::{ColorType, ImageFormat};
+
+/// Outcome of an FFI entry point. Returned directly (cast to `u32`) in place of the old
+/// convention of signalling failure via a zero length.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterStatus {
+    Ok = 0,
+    BadPointer = 1,
+    DecodeFailed = 2,
+    UnsupportedFormat = 3,
+    EncodeFailed = 4,
+    OutOfMemory = 5,
+}
+
+/// Metadata describing the encoded output image, written into the host's out region
+/// alongside the `[ptr, len]` pair so callers can size buffers and branch on dimensions or
+/// color layout without re-parsing the returned bytes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    pub color_type: u8,
+    pub format: u8,
+}
+
+impl ImageInfo {
+    pub fn new(width: u32, height: u32, color_type: ColorType, format: ImageFormat) -> Self {
+        Self {
+            width,
+            height,
+            color_type: color_type as u8,
+            format: format as u8,
+        }
+    }
+}
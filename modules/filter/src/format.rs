@@ -0,0 +1,124 @@
+//! FFI-facing tags for image container formats and pixel layouts, plus the
+//! encode/decode plumbing that maps them onto the `image` crate.
+
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{DynamicImage, ImageEncoder};
+
+/// Target/source container format, exposed to the host over FFI as a single byte.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png = 0,
+    Jpeg = 1,
+    WebP = 2,
+}
+
+impl ImageFormat {
+    /// Decode a raw tag byte received over FFI. Returns `None` for unrecognized tags.
+    pub fn from_u8(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Self::Png),
+            1 => Some(Self::Jpeg),
+            2 => Some(Self::WebP),
+            _ => None,
+        }
+    }
+
+    /// Parse a CLI-style format name (`"png"`, `"jpeg"`/`"jpg"`, `"webp"`), defaulting
+    /// behaviour is left to the caller.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "jpeg" | "jpg" => Some(Self::Jpeg),
+            "webp" => Some(Self::WebP),
+            _ => None,
+        }
+    }
+}
+
+impl From<image::ImageFormat> for ImageFormat {
+    fn from(fmt: image::ImageFormat) -> Self {
+        match fmt {
+            image::ImageFormat::Jpeg => Self::Jpeg,
+            image::ImageFormat::WebP => Self::WebP,
+            _ => Self::Png,
+        }
+    }
+}
+
+/// FFI-facing pixel layout. Variants 0-7 mirror `image::ColorType` one-to-one; 8-11 are
+/// channel-separated colorspaces produced by [`crate::colorspace::convert`] that the `image`
+/// crate has no container encoding for.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorType {
+    L8 = 0,
+    La8 = 1,
+    Rgb8 = 2,
+    Rgba8 = 3,
+    L16 = 4,
+    La16 = 5,
+    Rgb16 = 6,
+    Rgba16 = 7,
+    YCbCr8 = 8,
+    Hsl8 = 9,
+    Hsv8 = 10,
+    Cmyk8 = 11,
+}
+
+impl From<image::ColorType> for ColorType {
+    fn from(color: image::ColorType) -> Self {
+        match color {
+            image::ColorType::L8 => Self::L8,
+            image::ColorType::La8 => Self::La8,
+            image::ColorType::Rgb8 => Self::Rgb8,
+            image::ColorType::Rgba8 => Self::Rgba8,
+            image::ColorType::L16 => Self::L16,
+            image::ColorType::La16 => Self::La16,
+            image::ColorType::Rgb16 => Self::Rgb16,
+            image::ColorType::Rgba16 => Self::Rgba16,
+            _ => Self::Rgba8,
+        }
+    }
+}
+
+/// Encode `img` into `format`, honouring `quality` for lossy formats (0-100, JPEG only).
+pub fn encode(img: &DynamicImage, format: ImageFormat, quality: u8) -> image::ImageResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    let mut cursor = Cursor::new(&mut buf);
+    let (width, height) = (img.width(), img.height());
+    let color = img.color();
+
+    match format {
+        ImageFormat::Png => {
+            PngEncoder::new(&mut cursor).write_image(img.as_bytes(), width, height, color.into())?;
+        }
+        ImageFormat::Jpeg => {
+            // `JpegEncoder::write_image` only accepts `L8`/`Rgb8` — unlike
+            // `DynamicImage::write_to`/`save`, it does not run `make_compatible_img` first —
+            // so flatten everything else down to one of those before handing it the bytes.
+            let flattened = match color {
+                image::ColorType::L8 | image::ColorType::L16 => {
+                    DynamicImage::ImageLuma8(img.to_luma8())
+                }
+                _ => DynamicImage::ImageRgb8(img.to_rgb8()),
+            };
+            JpegEncoder::new_with_quality(&mut cursor, quality).write_image(
+                flattened.as_bytes(),
+                width,
+                height,
+                flattened.color().into(),
+            )?;
+        }
+        ImageFormat::WebP => {
+            WebPEncoder::new_lossless(&mut cursor)
+                .write_image(img.as_bytes(), width, height, color.into())?;
+        }
+    }
+
+    Ok(buf)
+}
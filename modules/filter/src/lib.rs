@@ -1,14 +1,32 @@
-use image::{DynamicImage, ImageFormat};
+use image::{DynamicImage, ImageFormat as LibImageFormat};
 use std::io::{Cursor, Read, Write};
 
-/// WASI/CLI entry point: read PNG from stdin, grayscale, write PNG to stdout.
-/// Processes a PNG image from stdin, converts it to grayscale, and writes the result to stdout.
+mod alloc;
+mod colorspace;
+mod error;
+mod format;
+mod ops;
+
+pub use alloc::{alloc, dealloc, realloc};
+pub use colorspace::ColorspaceTarget;
+pub use error::{FilterStatus, ImageInfo};
+pub use format::{ColorType, ImageFormat};
+pub use ops::Op;
+
+/// WASI/CLI entry point: read an image from stdin, grayscale, write the encoded result to
+/// stdout. The output container format is taken from the first CLI argument that names one
+/// (`png`, `jpeg`/`jpg`, `webp`), defaulting to PNG when no argument does.
 ///
 /// # Safety
 ///
 /// This function is unsafe because it directly interacts with the system's standard input and output.
 #[unsafe(no_mangle)]
 pub extern "C" fn process_stdin() {
+    let out_format = std::env::args()
+        .skip(1)
+        .find_map(|name| ImageFormat::from_name(&name))
+        .unwrap_or(ImageFormat::Png);
+
     // Read raw bytes from stdin
     let mut buffer = Vec::new();
     std::io::stdin()
@@ -28,97 +46,303 @@ pub extern "C" fn process_stdin() {
     let gray = img.to_luma8();
     let r#dyn = DynamicImage::ImageLuma8(gray);
 
-    // Encode back to PNG
-    let mut out_buf = Vec::new();
-    if let Err(e) = r#dyn.write_to(&mut Cursor::new(&mut out_buf), ImageFormat::Png) {
-        eprintln!("Failed to encode PNG: {}", e);
-        return;
-    }
+    // Encode to the requested output format
+    let out_buf = match format::encode(&r#dyn, out_format, 85) {
+        Ok(buf) => buf,
+        Err(e) => {
+            eprintln!("Failed to encode image: {}", e);
+            return;
+        }
+    };
 
     // Write to stdout
     if let Err(e) = std::io::stdout().write_all(&out_buf) {
-        eprintln!("Failed to write PNG to stdout: {}", e);
+        eprintln!("Failed to write image to stdout: {}", e);
+    }
+}
+
+/// In-RAM API: decode an image (auto-detecting the source container format), optionally
+/// grayscale it, and re-encode to `out_fmt`. On success, writes a freshly-allocated
+/// `[ptr, len]` pair followed by an `ImageInfo` describing the result into `out_ptr`'s
+/// region, and returns `FilterStatus::Ok`; any other `FilterStatus` variant means the out
+/// region was left untouched.
+///
+/// # Safety
+///
+/// The `input_ptr` must point to a valid byte slice of length `input_len`.
+/// The `out_ptr` must point to a valid memory location with enough space for two `u32`
+/// values followed by an `ImageInfo`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn convert(
+    input_ptr: *const u8,
+    input_len: usize,
+    out_fmt: u8,
+    quality: u8,
+    grayscale: u8,
+    out_ptr: *mut u32,
+) -> u32 {
+    if input_ptr.is_null() || out_ptr.is_null() {
+        return FilterStatus::BadPointer as u32;
+    }
+
+    // Safety: we trust the host passed a valid pointer
+    let input = unsafe { std::slice::from_raw_parts(input_ptr, input_len) };
+
+    let Some(target) = ImageFormat::from_u8(out_fmt) else {
+        return FilterStatus::UnsupportedFormat as u32;
+    };
+
+    // Auto-detect the source format up front so unsupported/corrupt input is rejected before
+    // we spend time decoding it.
+    if image::guess_format(input).is_err() {
+        return FilterStatus::DecodeFailed as u32;
+    }
+
+    let img = match image::load_from_memory(input) {
+        Ok(img) => img,
+        Err(_) => return FilterStatus::DecodeFailed as u32,
+    };
+
+    let img = if grayscale != 0 {
+        DynamicImage::ImageLuma8(img.to_luma8())
+    } else {
+        img
+    };
+
+    let (width, height) = (img.width(), img.height());
+    let color_type: ColorType = img.color().into();
+    // `format::encode` flattens JPEG output to `L8`/`Rgb8`, so report the encoded layout.
+    let color_type = match (target, color_type) {
+        (ImageFormat::Jpeg, ColorType::L8 | ColorType::L16) => ColorType::L8,
+        (ImageFormat::Jpeg, _) => ColorType::Rgb8,
+        (_, color_type) => color_type,
+    };
+
+    let buf = match format::encode(&img, target, quality) {
+        Ok(buf) => buf,
+        Err(_) => return FilterStatus::EncodeFailed as u32,
+    };
+
+    // Allocate WASM memory for output
+    let len = buf.len() as u32;
+    let ptr = alloc(len as usize);
+    if ptr.is_null() {
+        return FilterStatus::OutOfMemory as u32;
     }
+    let ptr = ptr as u32;
+
+    unsafe {
+        std::ptr::copy_nonoverlapping(buf.as_ptr(), ptr as *mut u8, len as usize);
+
+        // out_ptr points to two u32 slots [ptr, len] followed by an ImageInfo
+        std::ptr::write(out_ptr, ptr);
+        std::ptr::write(out_ptr.add(1), len);
+        std::ptr::write(
+            out_ptr.add(2) as *mut ImageInfo,
+            ImageInfo::new(width, height, color_type, target),
+        );
+    }
+
+    FilterStatus::Ok as u32
 }
 
-/// In-RAM API: take a PNG byte‐slice, return a freshly-allocated pointer+len to a PNG grayscale.
+/// In-RAM API: take a PNG byte-slice, grayscale it, and write a freshly-allocated
+/// `[ptr, len]` pair followed by an `ImageInfo` into `out_ptr`'s region. Returns a
+/// `FilterStatus`; any variant other than `Ok` means the out region was left untouched.
 ///
 /// # Safety
 ///
 /// The `input_ptr` must point to a valid PNG byte slice of length `input_len`.
-/// The `out_ptr` must point to a valid memory location with enough space to write two `u32` values.
+/// The `out_ptr` must point to a valid memory location with enough space for two `u32`
+/// values followed by an `ImageInfo`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn grayscale(
     input_ptr: *const u8,
     input_len: usize,
     out_ptr: *mut u32,
 ) -> u32 {
+    if input_ptr.is_null() || out_ptr.is_null() {
+        return FilterStatus::BadPointer as u32;
+    }
+
     // Safety: we trust the host passed a valid pointer
     let input = unsafe { std::slice::from_raw_parts(input_ptr, input_len) };
 
     // Decode
     let img = match image::load_from_memory(input) {
         Ok(img) => img,
-        Err(_) => return 0, // return 0 length on error
+        Err(_) => return FilterStatus::DecodeFailed as u32,
     };
 
     // Grayscale
     let gray = img.to_luma8();
+    let (width, height) = (gray.width(), gray.height());
     let r#dyn = DynamicImage::ImageLuma8(gray);
 
     // Encode to PNG
     let mut buf = Vec::new();
     if r#dyn
-        .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+        .write_to(&mut Cursor::new(&mut buf), LibImageFormat::Png)
         .is_err()
     {
-        return 0;
+        return FilterStatus::EncodeFailed as u32;
     }
 
     // Allocate WASM memory for output
     let len = buf.len() as u32;
-    let ptr = alloc(len as usize) as u32;
+    let ptr = alloc(len as usize);
+    if ptr.is_null() {
+        return FilterStatus::OutOfMemory as u32;
+    }
+    let ptr = ptr as u32;
 
-    // Copy data into WASM memory
     unsafe {
         std::ptr::copy_nonoverlapping(buf.as_ptr(), ptr as *mut u8, len as usize);
-    }
 
-    // Write back pointer and length to caller’s out_ptr region
-    unsafe {
-        // out_ptr points to two u32 slots: [ptr, len]
+        // out_ptr points to two u32 slots [ptr, len] followed by an ImageInfo
         std::ptr::write(out_ptr, ptr);
         std::ptr::write(out_ptr.add(1), len);
+        std::ptr::write(
+            out_ptr.add(2) as *mut ImageInfo,
+            ImageInfo::new(width, height, ColorType::L8, ImageFormat::Png),
+        );
     }
 
-    // Return length (for convenience)
-    len
+    FilterStatus::Ok as u32
 }
 
-/// Allocate `size` bytes in WASM linear memory and return the base pointer.
+/// In-RAM API: decode `input`, fold the serialized op-list at `op_ptr`/`op_len` (see
+/// [`ops::parse`]) over it in order, then encode the result back to PNG. Writes a
+/// freshly-allocated `[ptr, len]` pair followed by an `ImageInfo` into `out_ptr`'s region on
+/// success. This is the composable-pipeline counterpart to the single-transform `grayscale`
+/// and `convert` entry points: one call can run an arbitrary chain of ops.
 ///
 /// # Safety
 ///
-/// This function is unsafe because it directly interacts with WASM linear memory.
+/// `input_ptr`/`input_len` must describe a valid byte slice, as must `op_ptr`/`op_len`. The
+/// `out_ptr` must point to a valid memory location with enough space for two `u32` values
+/// followed by an `ImageInfo`.
 #[unsafe(no_mangle)]
-pub extern "C" fn alloc(size: usize) -> *mut u8 {
-    let mut buf = Vec::with_capacity(size);
-    let ptr = buf.as_mut_ptr();
-    std::mem::forget(buf);
-    ptr
+pub unsafe extern "C" fn apply(
+    input_ptr: *const u8,
+    input_len: usize,
+    op_ptr: *const u8,
+    op_len: usize,
+    out_ptr: *mut u32,
+) -> u32 {
+    if input_ptr.is_null() || op_ptr.is_null() || out_ptr.is_null() {
+        return FilterStatus::BadPointer as u32;
+    }
+
+    // Safety: we trust the host passed valid pointers
+    let input = unsafe { std::slice::from_raw_parts(input_ptr, input_len) };
+    let op_bytes = unsafe { std::slice::from_raw_parts(op_ptr, op_len) };
+
+    let Some(op_list) = ops::parse(op_bytes) else {
+        return FilterStatus::UnsupportedFormat as u32;
+    };
+
+    let img = match image::load_from_memory(input) {
+        Ok(img) => img,
+        Err(_) => return FilterStatus::DecodeFailed as u32,
+    };
+
+    let img = ops::fold(img, &op_list);
+    let (width, height) = (img.width(), img.height());
+    let color_type: ColorType = img.color().into();
+
+    let buf = match format::encode(&img, ImageFormat::Png, 100) {
+        Ok(buf) => buf,
+        Err(_) => return FilterStatus::EncodeFailed as u32,
+    };
+
+    // Allocate WASM memory for output
+    let len = buf.len() as u32;
+    let ptr = alloc(len as usize);
+    if ptr.is_null() {
+        return FilterStatus::OutOfMemory as u32;
+    }
+    let ptr = ptr as u32;
+
+    unsafe {
+        std::ptr::copy_nonoverlapping(buf.as_ptr(), ptr as *mut u8, len as usize);
+
+        // out_ptr points to two u32 slots [ptr, len] followed by an ImageInfo
+        std::ptr::write(out_ptr, ptr);
+        std::ptr::write(out_ptr.add(1), len);
+        std::ptr::write(
+            out_ptr.add(2) as *mut ImageInfo,
+            ImageInfo::new(width, height, color_type, ImageFormat::Png),
+        );
+    }
+
+    FilterStatus::Ok as u32
 }
 
-/// Free a previously allocated buffer.
+/// In-RAM API: decode `input` and convert it to `target`'s colorspace (see
+/// [`ColorspaceTarget`]), writing the channel-separated bytes — not a re-encoded container
+/// format — to a freshly-allocated `[ptr, len]` pair followed by an `ImageInfo` at `out_ptr`.
+/// The `ImageInfo.color_type` field carries the resulting channel layout (e.g. `Hsl8`,
+/// `Cmyk8`) so downstream consumers know how to interpret the bytes. Since those bytes have no
+/// container format of their own, `ImageInfo.format` carries the detected source format
+/// (`Jpeg` or `WebP`, with every other source reported as `Png`).
 ///
 /// # Safety
 ///
-/// The `ptr` must point to a valid memory address that was previously allocated by `alloc`.
-/// The `size` must match the size that was used when `alloc` was called.
+/// `input_ptr`/`input_len` must describe a valid byte slice. `out_ptr` must point to a valid
+/// memory location with enough space for two `u32` values followed by an `ImageInfo`.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn dealloc(ptr: *mut u8, size: usize) {
+pub unsafe extern "C" fn to_colorspace(
+    input_ptr: *const u8,
+    input_len: usize,
+    target: u8,
+    out_ptr: *mut u32,
+) -> u32 {
+    if input_ptr.is_null() || out_ptr.is_null() {
+        return FilterStatus::BadPointer as u32;
+    }
+
+    // Safety: we trust the host passed a valid pointer
+    let input = unsafe { std::slice::from_raw_parts(input_ptr, input_len) };
+
+    let Some(target) = ColorspaceTarget::from_u8(target) else {
+        return FilterStatus::UnsupportedFormat as u32;
+    };
+
+    let source_format = match image::guess_format(input) {
+        Ok(fmt) => ImageFormat::from(fmt),
+        Err(_) => return FilterStatus::DecodeFailed as u32,
+    };
+
+    let img = match image::load_from_memory(input) {
+        Ok(img) => img,
+        Err(_) => return FilterStatus::DecodeFailed as u32,
+    };
+    let (width, height) = (img.width(), img.height());
+
+    let (buf, color_type) = colorspace::convert(&img, target);
+
+    // Allocate WASM memory for output
+    let len = buf.len() as u32;
+    let ptr = alloc(len as usize);
+    if ptr.is_null() {
+        return FilterStatus::OutOfMemory as u32;
+    }
+    let ptr = ptr as u32;
+
     unsafe {
-        let _ = Vec::from_raw_parts(ptr, 0, size);
+        std::ptr::copy_nonoverlapping(buf.as_ptr(), ptr as *mut u8, len as usize);
+
+        // out_ptr points to two u32 slots [ptr, len] followed by an ImageInfo
+        std::ptr::write(out_ptr, ptr);
+        std::ptr::write(out_ptr.add(1), len);
+        std::ptr::write(
+            out_ptr.add(2) as *mut ImageInfo,
+            ImageInfo::new(width, height, color_type, source_format),
+        );
     }
+
+    FilterStatus::Ok as u32
 }
 
 #[unsafe(no_mangle)]
@@ -0,0 +1,178 @@
+//! Serialized filter-operation list: a small op-code format the host encodes once so a whole
+//! pipeline (grayscale, resize, rotate, ...) can be folded over a decoded image and encoded
+//! back out in a single `apply()` call, instead of round-tripping through WASM per step.
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+
+/// A single step in an `apply()` pipeline, decoded from the host's serialized op-list.
+#[derive(Debug, Clone, Copy)]
+pub enum Op {
+    Grayscale,
+    Resize {
+        width: u32,
+        height: u32,
+        filter: FilterType,
+    },
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    Blur {
+        sigma: f32,
+    },
+    Brighten {
+        delta: i32,
+    },
+    Contrast {
+        contrast: f32,
+    },
+    Invert,
+    FlipH,
+    FlipV,
+}
+
+/// Parse the host's serialized op-list: a tag byte per op followed by its fixed-size
+/// little-endian params, back to back until the buffer is exhausted. Returns `None` on any
+/// unrecognized tag or truncated parameter.
+///
+/// `Rotate`'s single param byte is a quarter-turn count (`1` = 90°, `2` = 180°, `3` = 270°)
+/// rather than the degree value itself, since 270 doesn't fit in a `u8`.
+pub fn parse(mut bytes: &[u8]) -> Option<Vec<Op>> {
+    let mut ops = Vec::new();
+
+    while let Some((&tag, rest)) = bytes.split_first() {
+        bytes = rest;
+        let op = match tag {
+            0 => Op::Grayscale,
+            1 => Op::Resize {
+                width: take_u32(&mut bytes)?,
+                height: take_u32(&mut bytes)?,
+                filter: filter_from_u8(take_u8(&mut bytes)?)?,
+            },
+            2 => match take_u8(&mut bytes)? {
+                1 => Op::Rotate90,
+                2 => Op::Rotate180,
+                3 => Op::Rotate270,
+                _ => return None,
+            },
+            3 => Op::Crop {
+                x: take_u32(&mut bytes)?,
+                y: take_u32(&mut bytes)?,
+                width: take_u32(&mut bytes)?,
+                height: take_u32(&mut bytes)?,
+            },
+            4 => {
+                let sigma = take_f32(&mut bytes)?;
+                // `GaussianBlurParameters::new_from_sigma` panics on a non-finite or negative
+                // sigma, and sigma is otherwise unvalidated host input, so reject it here.
+                if !sigma.is_finite() || sigma < 0.0 {
+                    return None;
+                }
+                Op::Blur { sigma }
+            }
+            5 => Op::Brighten {
+                delta: take_i32(&mut bytes)?,
+            },
+            6 => Op::Contrast {
+                contrast: take_f32(&mut bytes)?,
+            },
+            7 => Op::Invert,
+            8 => Op::FlipH,
+            9 => Op::FlipV,
+            _ => return None,
+        };
+        ops.push(op);
+    }
+
+    Some(ops)
+}
+
+/// Fold the op-list over `img` in order, returning the transformed image.
+pub fn fold(mut img: DynamicImage, ops: &[Op]) -> DynamicImage {
+    for op in ops {
+        img = match *op {
+            Op::Grayscale => DynamicImage::ImageLuma8(img.to_luma8()),
+            Op::Resize {
+                width,
+                height,
+                filter,
+            } => img.resize_exact(width, height, filter),
+            Op::Rotate90 => img.rotate90(),
+            Op::Rotate180 => img.rotate180(),
+            Op::Rotate270 => img.rotate270(),
+            Op::Crop {
+                x,
+                y,
+                width,
+                height,
+            } => img.crop_imm(x, y, width, height),
+            // A preceding Resize/Crop can clip to a zero-area image (e.g. an out-of-bounds
+            // crop), and `image` panics rather than erroring when Blur/Contrast run over one —
+            // no-op instead of folding into that panic.
+            Op::Blur { sigma } => {
+                if img.width() == 0 || img.height() == 0 {
+                    img
+                } else {
+                    img.blur(sigma)
+                }
+            }
+            Op::Brighten { delta } => img.brighten(delta),
+            Op::Contrast { contrast } => {
+                if img.width() == 0 || img.height() == 0 {
+                    img
+                } else {
+                    img.adjust_contrast(contrast)
+                }
+            }
+            Op::Invert => {
+                img.invert();
+                img
+            }
+            Op::FlipH => img.fliph(),
+            Op::FlipV => img.flipv(),
+        };
+    }
+    img
+}
+
+fn filter_from_u8(tag: u8) -> Option<FilterType> {
+    match tag {
+        0 => Some(FilterType::Nearest),
+        1 => Some(FilterType::Triangle),
+        2 => Some(FilterType::CatmullRom),
+        3 => Some(FilterType::Gaussian),
+        4 => Some(FilterType::Lanczos3),
+        _ => None,
+    }
+}
+
+fn take<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+    if bytes.len() < N {
+        return None;
+    }
+    let (chunk, rest) = bytes.split_at(N);
+    *bytes = rest;
+    chunk.try_into().ok()
+}
+
+fn take_u8(bytes: &mut &[u8]) -> Option<u8> {
+    take::<1>(bytes).map(|b| b[0])
+}
+
+fn take_u32(bytes: &mut &[u8]) -> Option<u32> {
+    take::<4>(bytes).map(u32::from_le_bytes)
+}
+
+fn take_i32(bytes: &mut &[u8]) -> Option<i32> {
+    take::<4>(bytes).map(i32::from_le_bytes)
+}
+
+fn take_f32(bytes: &mut &[u8]) -> Option<f32> {
+    take::<4>(bytes).map(f32::from_le_bytes)
+}
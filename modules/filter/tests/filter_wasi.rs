@@ -1,6 +1,6 @@
 use image::{DynamicImage, ImageBuffer, Rgba, ImageFormat};
 use std::io::Cursor;
-use filter::{grayscale, alloc, dealloc};
+use filter::{apply, convert, grayscale, to_colorspace, alloc, realloc, dealloc, ColorspaceTarget, FilterStatus, ImageInfo};
 
 fn create_sample_png() -> Vec<u8> {
     let img: ImageBuffer<Rgba<u8>, _> =
@@ -23,12 +23,44 @@ fn test_alloc_and_dealloc() {
     }
 }
 
+#[test]
+fn test_realloc_grows_and_preserves_contents() {
+    let old_size = 8;
+    let ptr = alloc(old_size);
+    unsafe { std::ptr::write_bytes(ptr, 0xCD, old_size) };
+
+    let new_size = 256;
+    let ptr = unsafe { realloc(ptr, old_size, new_size) };
+    assert!(!ptr.is_null(), "Reallocation returned null pointer");
+
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, old_size) };
+    assert!(bytes.iter().all(|&b| b == 0xCD), "Contents were not preserved across realloc");
+
+    unsafe { dealloc(ptr, new_size) };
+}
+
+#[test]
+fn test_dealloc_ignores_mismatched_caller_size() {
+    // dealloc must free based on the registry's tracked capacity, not whatever `size` the
+    // caller happens to pass, so a stale/wrong size can't cause unsound frees.
+    let size = 128;
+    let ptr = alloc(size);
+    unsafe { dealloc(ptr, 1) };
+}
+
+/// Out region for `grayscale`/`convert`/`apply`: two `u32` slots `[ptr, len]` followed by an
+/// `ImageInfo` (12 bytes), sized generously in `u32` units so the `ImageInfo` write never
+/// overruns it.
+fn out_region() -> [u32; 6] {
+    [0u32; 6]
+}
+
 #[test]
 fn test_invalid_input_grayscale() {
     let bad = b"not a png";
-    let mut out = [0u32; 2];
-    let len = unsafe { grayscale(bad.as_ptr(), bad.len(), out.as_mut_ptr()) };
-    assert_eq!(len, 0, "Expected zero length for invalid input");
+    let mut out = out_region();
+    let status = unsafe { grayscale(bad.as_ptr(), bad.len(), out.as_mut_ptr()) };
+    assert_eq!(status, FilterStatus::DecodeFailed as u32);
 }
 
 #[test]
@@ -37,9 +69,9 @@ fn test_grayscale_round_trip() {
     let mut png = Vec::new();
     img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).unwrap();
 
-    let mut out = [0u32; 2];
-    let len = unsafe { grayscale(png.as_ptr(), png.len(), out.as_mut_ptr()) };
-    assert!(len > 0);
+    let mut out = out_region();
+    let status = unsafe { grayscale(png.as_ptr(), png.len(), out.as_mut_ptr()) };
+    assert_eq!(status, FilterStatus::Ok as u32);
 
     let ptr = out[0] as usize;
     let len_usize = out[1] as usize;
@@ -48,20 +80,304 @@ fn test_grayscale_round_trip() {
     let gray = image::load_from_memory(slice).unwrap();
     assert_eq!(gray.color(), image::ColorType::L8);
 
+    let info = unsafe { std::ptr::read(out.as_ptr().add(2) as *const ImageInfo) };
+    assert_eq!((info.width, info.height), (2, 2));
+
     unsafe { dealloc(out[0] as *mut u8, len_usize) };
 }
 
 #[test]
 fn test_grayscale_conversion() {
     let input = create_sample_png();
-    let mut out = [0u32; 2];
-    let len = unsafe { grayscale(input.as_ptr(), input.len(), out.as_mut_ptr()) };
-    assert!(len > 0);
+    let mut out = out_region();
+    let status = unsafe { grayscale(input.as_ptr(), input.len(), out.as_mut_ptr()) };
+    assert_eq!(status, FilterStatus::Ok as u32);
 
     let ptr = out[0] as usize;
     let len_usize = out[1] as usize;
-    let slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len_usize) };    let gray = image::load_from_memory(slice).unwrap();
+    let slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len_usize) };
+    let gray = image::load_from_memory(slice).unwrap();
     assert_eq!(gray.color(), image::ColorType::L8);
 
     unsafe { dealloc(ptr as *mut u8, len_usize) };
 }
+
+#[test]
+fn test_apply_grayscale_then_fliph_pipeline() {
+    let input = create_sample_png();
+    // op-list: tag 0 (Grayscale), tag 8 (FlipH)
+    let op_list = [0u8, 8u8];
+
+    let mut out = out_region();
+    let status = unsafe {
+        apply(
+            input.as_ptr(),
+            input.len(),
+            op_list.as_ptr(),
+            op_list.len(),
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::Ok as u32);
+
+    let ptr = out[0] as usize;
+    let len_usize = out[1] as usize;
+    let slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len_usize) };
+    let result = image::load_from_memory(slice).unwrap();
+    assert_eq!(result.color(), image::ColorType::L8);
+
+    let info = unsafe { std::ptr::read(out.as_ptr().add(2) as *const ImageInfo) };
+    assert_eq!((info.width, info.height), (2, 2));
+
+    unsafe { dealloc(ptr as *mut u8, len_usize) };
+}
+
+#[test]
+fn test_to_colorspace_ycbcr() {
+    let input = create_sample_png();
+    let mut out = out_region();
+    let status = unsafe {
+        to_colorspace(
+            input.as_ptr(),
+            input.len(),
+            ColorspaceTarget::YCbCr as u8,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::Ok as u32);
+
+    let ptr = out[0] as usize;
+    let len_usize = out[1] as usize;
+    // 2x2 image, 3 bytes (Y, Cb, Cr) per pixel
+    assert_eq!(len_usize, 2 * 2 * 3);
+
+    let info = unsafe { std::ptr::read(out.as_ptr().add(2) as *const ImageInfo) };
+    assert_eq!(info.color_type, filter::ColorType::YCbCr8 as u8);
+
+    unsafe { dealloc(ptr as *mut u8, len_usize) };
+}
+
+#[test]
+fn test_to_colorspace_rejects_unknown_target() {
+    let input = create_sample_png();
+    let mut out = out_region();
+    let status = unsafe { to_colorspace(input.as_ptr(), input.len(), 0xFF, out.as_mut_ptr()) };
+    assert_eq!(status, FilterStatus::UnsupportedFormat as u32);
+}
+
+#[test]
+fn test_convert_png_to_jpeg_round_trip() {
+    let input = create_sample_png();
+    let mut out = out_region();
+    let status = unsafe {
+        convert(
+            input.as_ptr(),
+            input.len(),
+            filter::ImageFormat::Jpeg as u8,
+            85,
+            0,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::Ok as u32);
+
+    let ptr = out[0] as usize;
+    let len_usize = out[1] as usize;
+    let slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len_usize) };
+    assert_eq!(image::guess_format(slice).unwrap(), ImageFormat::Jpeg);
+
+    let decoded = image::load_from_memory(slice).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (2, 2));
+
+    let info = unsafe { std::ptr::read(out.as_ptr().add(2) as *const ImageInfo) };
+    assert_eq!(info.format, filter::ImageFormat::Jpeg as u8);
+
+    unsafe { dealloc(ptr as *mut u8, len_usize) };
+}
+
+#[test]
+fn test_convert_honors_jpeg_quality() {
+    let input = create_sample_png();
+
+    let mut low_out = out_region();
+    let low_status = unsafe {
+        convert(
+            input.as_ptr(),
+            input.len(),
+            filter::ImageFormat::Jpeg as u8,
+            1,
+            0,
+            low_out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(low_status, FilterStatus::Ok as u32);
+
+    let mut high_out = out_region();
+    let high_status = unsafe {
+        convert(
+            input.as_ptr(),
+            input.len(),
+            filter::ImageFormat::Jpeg as u8,
+            100,
+            0,
+            high_out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(high_status, FilterStatus::Ok as u32);
+
+    // Higher quality should never produce a smaller encoding than the lowest quality setting.
+    assert!(high_out[1] >= low_out[1]);
+
+    unsafe {
+        dealloc(low_out[0] as *mut u8, low_out[1] as usize);
+        dealloc(high_out[0] as *mut u8, high_out[1] as usize);
+    }
+}
+
+#[test]
+fn test_convert_to_webp() {
+    let input = create_sample_png();
+    let mut out = out_region();
+    let status = unsafe {
+        convert(
+            input.as_ptr(),
+            input.len(),
+            filter::ImageFormat::WebP as u8,
+            100,
+            0,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::Ok as u32);
+
+    let ptr = out[0] as usize;
+    let len_usize = out[1] as usize;
+    let slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len_usize) };
+    assert_eq!(image::guess_format(slice).unwrap(), ImageFormat::WebP);
+
+    unsafe { dealloc(ptr as *mut u8, len_usize) };
+}
+
+#[test]
+fn test_convert_with_grayscale_flag() {
+    let input = create_sample_png();
+    let mut out = out_region();
+    let status = unsafe {
+        convert(
+            input.as_ptr(),
+            input.len(),
+            filter::ImageFormat::Png as u8,
+            100,
+            1,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::Ok as u32);
+
+    let ptr = out[0] as usize;
+    let len_usize = out[1] as usize;
+    let slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len_usize) };
+    let decoded = image::load_from_memory(slice).unwrap();
+    assert_eq!(decoded.color(), image::ColorType::L8);
+
+    let info = unsafe { std::ptr::read(out.as_ptr().add(2) as *const ImageInfo) };
+    assert_eq!(info.color_type, filter::ColorType::L8 as u8);
+
+    unsafe { dealloc(ptr as *mut u8, len_usize) };
+}
+
+#[test]
+fn test_convert_rejects_unknown_target_format() {
+    let input = create_sample_png();
+    let mut out = out_region();
+    let status = unsafe { convert(input.as_ptr(), input.len(), 0xFF, 100, 0, out.as_mut_ptr()) };
+    assert_eq!(status, FilterStatus::UnsupportedFormat as u32);
+}
+
+#[test]
+fn test_convert_rejects_invalid_input() {
+    let bad = b"not an image";
+    let mut out = out_region();
+    let status = unsafe {
+        convert(
+            bad.as_ptr(),
+            bad.len(),
+            filter::ImageFormat::Png as u8,
+            100,
+            0,
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::DecodeFailed as u32);
+}
+
+#[test]
+fn test_apply_rejects_invalid_blur_sigma() {
+    let input = create_sample_png();
+    // tag 4 (Blur) with a negative sigma (-1.0f32 little-endian)
+    let op_list = [4u8];
+    let op_list: Vec<u8> = op_list
+        .iter()
+        .copied()
+        .chain((-1.0f32).to_le_bytes())
+        .collect();
+
+    let mut out = out_region();
+    let status = unsafe {
+        apply(
+            input.as_ptr(),
+            input.len(),
+            op_list.as_ptr(),
+            op_list.len(),
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::UnsupportedFormat as u32);
+}
+
+#[test]
+fn test_apply_blur_after_zero_area_crop_does_not_panic() {
+    let input = create_sample_png();
+    // tag 3 (Crop) entirely outside the 2x2 source, clipped by `image` to 0x0, then
+    // tag 4 (Blur) with sigma 1.0 — must not panic on the degenerate image.
+    let mut op_list = vec![3u8];
+    op_list.extend(10u32.to_le_bytes()); // x
+    op_list.extend(10u32.to_le_bytes()); // y
+    op_list.extend(5u32.to_le_bytes()); // width
+    op_list.extend(5u32.to_le_bytes()); // height
+    op_list.push(4u8);
+    op_list.extend(1.0f32.to_le_bytes());
+
+    let mut out = out_region();
+    let status = unsafe {
+        apply(
+            input.as_ptr(),
+            input.len(),
+            op_list.as_ptr(),
+            op_list.len(),
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::Ok as u32);
+
+    unsafe { dealloc(out[0] as *mut u8, out[1] as usize) };
+}
+
+#[test]
+fn test_apply_rejects_malformed_op_list() {
+    let input = create_sample_png();
+    // tag 1 (Resize) declares width/height/filter params but the buffer is truncated
+    let op_list = [1u8, 0u8, 0u8];
+
+    let mut out = out_region();
+    let status = unsafe {
+        apply(
+            input.as_ptr(),
+            input.len(),
+            op_list.as_ptr(),
+            op_list.len(),
+            out.as_mut_ptr(),
+        )
+    };
+    assert_eq!(status, FilterStatus::UnsupportedFormat as u32);
+}
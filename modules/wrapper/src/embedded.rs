@@ -0,0 +1,111 @@
+//! In-process host mode: instantiate the filter module with an embedded runtime and drive it
+//! over the `alloc`/`grayscale`/`dealloc` linear-memory ABI, instead of piping stdin/stdout
+//! through a spawned `wasmedge` process.
+
+use std::io;
+
+use wasmtime::{Engine, Instance, Module, Store, TypedFunc};
+
+use crate::mem::{self, MemoryAccessError};
+
+/// Out region big enough for `grayscale`'s `[ptr: u32, len: u32]` pair followed by an
+/// `ImageInfo` (`width: u32, height: u32, color_type: u8, format: u8`, repr(C)-padded to 12
+/// bytes) — 20 bytes, rounded up to 24 for headroom.
+const OUT_REGION_SIZE: u32 = 24;
+
+/// `FilterStatus::Ok` from `filter::error` — mirrored here since the host only needs to
+/// distinguish success from failure, not decode every variant.
+const FILTER_STATUS_OK: u32 = 0;
+
+#[derive(Debug)]
+pub enum EmbeddedHostError {
+    Wasm(wasmtime::Error),
+    Memory(MemoryAccessError),
+    MissingExport(&'static str),
+    GuestReturnedError(u32),
+}
+
+impl std::fmt::Display for EmbeddedHostError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wasm(e) => write!(f, "wasm runtime error: {e}"),
+            Self::Memory(e) => write!(f, "guest memory access rejected: {e}"),
+            Self::MissingExport(name) => write!(f, "module does not export `{name}`"),
+            Self::GuestReturnedError(status) => write!(f, "grayscale failed with status {status}"),
+        }
+    }
+}
+
+impl std::error::Error for EmbeddedHostError {}
+
+impl From<wasmtime::Error> for EmbeddedHostError {
+    fn from(e: wasmtime::Error) -> Self {
+        Self::Wasm(e)
+    }
+}
+
+impl From<MemoryAccessError> for EmbeddedHostError {
+    fn from(e: MemoryAccessError) -> Self {
+        Self::Memory(e)
+    }
+}
+
+/// Run the `grayscale` ABI against `wasm_path` entirely in-process: instantiate the module,
+/// push `input` into guest linear memory, invoke `grayscale`, and pull the encoded result back
+/// out — all guest-memory access going through the bounds-checked [`mem`] accessors so a
+/// malformed `ptr`/`len` from the guest can't make the host read outside the sandbox.
+pub fn run_grayscale(wasm_path: &str, input: &[u8]) -> Result<Vec<u8>, EmbeddedHostError> {
+    let engine = Engine::default();
+    let module = Module::from_file(&engine, wasm_path)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or(EmbeddedHostError::MissingExport("memory"))?;
+    let alloc: TypedFunc<u32, u32> = get_func(&instance, &mut store, "alloc")?;
+    let grayscale: TypedFunc<(u32, u32, u32), u32> = get_func(&instance, &mut store, "grayscale")?;
+    let dealloc: TypedFunc<(u32, u32), ()> = get_func(&instance, &mut store, "dealloc")?;
+
+    let input_ptr = alloc.call(&mut store, input.len() as u32)?;
+    mem::write(&memory, &mut store, input_ptr, input)?;
+
+    let out_ptr = alloc.call(&mut store, OUT_REGION_SIZE)?;
+
+    let status = grayscale.call(&mut store, (input_ptr, input.len() as u32, out_ptr))?;
+    if status != FILTER_STATUS_OK {
+        dealloc.call(&mut store, (input_ptr, input.len() as u32))?;
+        dealloc.call(&mut store, (out_ptr, OUT_REGION_SIZE))?;
+        return Err(EmbeddedHostError::GuestReturnedError(status));
+    }
+
+    let result_ptr = mem::read_u32(&memory, &store, out_ptr)?;
+    let result_len = mem::read_u32(&memory, &store, out_ptr + 4)?;
+    let result = mem::read(&memory, &store, result_ptr, result_len)?;
+
+    dealloc.call(&mut store, (input_ptr, input.len() as u32))?;
+    dealloc.call(&mut store, (out_ptr, OUT_REGION_SIZE))?;
+    dealloc.call(&mut store, (result_ptr, result_len))?;
+
+    Ok(result)
+}
+
+fn get_func<Params, Results>(
+    instance: &Instance,
+    store: &mut Store<()>,
+    name: &'static str,
+) -> Result<TypedFunc<Params, Results>, EmbeddedHostError>
+where
+    Params: wasmtime::WasmParams,
+    Results: wasmtime::WasmResults,
+{
+    instance
+        .get_typed_func(store, name)
+        .map_err(|_| EmbeddedHostError::MissingExport(name))
+}
+
+impl From<EmbeddedHostError> for io::Error {
+    fn from(e: EmbeddedHostError) -> Self {
+        io::Error::other(e.to_string())
+    }
+}
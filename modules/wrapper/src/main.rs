@@ -1,21 +1,64 @@
+mod embedded;
+mod mem;
+
 use std::{
     process::{Command, Stdio},
-    fs::File,
+    fs::{self, File},
     io::{self, Read},
 };
 
 fn main() -> io::Result<()> {
-    let mut args = std::env::args().skip(1);
-    let wasm_path   = args.next().expect("Missing wasm module path");
-    let input_path  = args.next().expect("Missing input file path");
-    let output_path = args.next().expect("Missing output file path");
+    let mut positional = Vec::new();
+    let mut subprocess = false;
+    for arg in std::env::args().skip(1) {
+        if arg == "--subprocess" {
+            subprocess = true;
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let wasm_path = positional.next().expect("Missing wasm module path");
+    let input_path = positional.next().expect("Missing input file path");
+    let output_path = positional.next().expect("Missing output file path");
+    let format = positional.next();
+
+    if subprocess {
+        run_subprocess(&wasm_path, &input_path, &output_path, format.as_deref())
+    } else {
+        run_embedded(&wasm_path, &input_path, &output_path)
+    }
+}
+
+/// Default mode: instantiate the module in-process and drive it over the `alloc`/
+/// `grayscale`/`dealloc` linear-memory ABI, with every guest-memory access bounds-checked.
+fn run_embedded(wasm_path: &str, input_path: &str, output_path: &str) -> io::Result<()> {
+    let input = fs::read(input_path)?;
+    let output = embedded::run_grayscale(wasm_path, &input)?;
+    fs::write(output_path, output)
+}
 
-    let mut input  = File::open(&input_path)?;
-    let mut output = File::create(&output_path)?;
+/// Fallback mode (`--subprocess`): shell out to the `wasmedge` binary and pipe the image
+/// through its stdin/stdout, as the host originally did. `format`, if given, is forwarded as
+/// `process_stdin`'s second CLI argument so the guest re-encodes to that container format
+/// instead of defaulting to PNG.
+fn run_subprocess(
+    wasm_path: &str,
+    input_path: &str,
+    output_path: &str,
+    format: Option<&str>,
+) -> io::Result<()> {
+    let mut input = File::open(input_path)?;
+    let mut output = File::create(output_path)?;
+
+    let mut command = Command::new("wasmedge");
+    command.arg(&wasm_path).arg("process_stdin");
+    if let Some(format) = format {
+        command.arg(format);
+    }
 
-    let mut child = Command::new("wasmedge")
-        .arg(&wasm_path)
-        .arg("process_stdin")
+    let mut child = command
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
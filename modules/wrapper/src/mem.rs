@@ -0,0 +1,145 @@
+//! Bounds-checked linear-memory access for the embedded host. A guest module is untrusted:
+//! if it returns a `ptr`/`len` pair that lies outside its own heap, the host must refuse to
+//! read or write there rather than forming a raw slice straight from the guest pointer.
+
+use std::fmt;
+
+use wasmtime::{AsContext, AsContextMut, Memory};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAccessError {
+    /// `offset + len` falls outside the memory's current size.
+    HeapOutOfBounds,
+    /// `offset + len` overflowed before it could even be compared against the heap size.
+    Overflow,
+}
+
+impl fmt::Display for MemoryAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HeapOutOfBounds => write!(f, "guest pointer/length falls outside linear memory"),
+            Self::Overflow => write!(f, "guest pointer/length arithmetic overflowed"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryAccessError {}
+
+/// Copy `len` bytes out of `memory` starting at `offset`, after checking the range against
+/// the memory's current size.
+pub fn read<T>(
+    memory: &Memory,
+    store: impl AsContext<Data = T>,
+    offset: u32,
+    len: u32,
+) -> Result<Vec<u8>, MemoryAccessError> {
+    let end = offset
+        .checked_add(len)
+        .ok_or(MemoryAccessError::Overflow)?;
+
+    let data = memory.data(store.as_context());
+    if end > data.len() as u64 {
+        return Err(MemoryAccessError::HeapOutOfBounds);
+    }
+
+    Ok(data[offset as usize..end as usize].to_vec())
+}
+
+/// Write `bytes` into `memory` starting at `offset`, after checking the range against the
+/// memory's current size.
+pub fn write<T>(
+    memory: &Memory,
+    mut store: impl AsContextMut<Data = T>,
+    offset: u32,
+    bytes: &[u8],
+) -> Result<(), MemoryAccessError> {
+    let end = u32::try_from(bytes.len())
+        .ok()
+        .and_then(|len| offset.checked_add(len))
+        .ok_or(MemoryAccessError::Overflow)?;
+
+    let data = memory.data_mut(store.as_context_mut());
+    if end > data.len() as u64 {
+        return Err(MemoryAccessError::HeapOutOfBounds);
+    }
+
+    data[offset as usize..end as usize].copy_from_slice(bytes);
+    Ok(())
+}
+
+/// Read a little-endian `u32` out of `memory` at `offset`, bounds-checked like [`read`].
+pub fn read_u32<T>(
+    memory: &Memory,
+    store: impl AsContext<Data = T>,
+    offset: u32,
+) -> Result<u32, MemoryAccessError> {
+    let bytes = read(memory, store, offset, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().expect("read(.., 4) returns 4 bytes")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmtime::{Engine, MemoryType, Store};
+
+    /// A single-page (64 KiB) standalone `Memory`, with no guest module attached — enough to
+    /// exercise the bounds-check logic directly.
+    fn test_memory() -> (Store<()>, Memory) {
+        let engine = Engine::default();
+        let mut store = Store::new(&engine, ());
+        let memory = Memory::new(&mut store, MemoryType::new(1, None)).unwrap();
+        (store, memory)
+    }
+
+    #[test]
+    fn read_returns_bytes_within_bounds() {
+        let (mut store, memory) = test_memory();
+        write(&memory, &mut store, 0, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(read(&memory, &store, 0, 4).unwrap(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn read_rejects_out_of_bounds_offset() {
+        let (store, memory) = test_memory();
+        let size = memory.data(&store).len() as u32;
+        let err = read(&memory, &store, size, 1).unwrap_err();
+        assert_eq!(err, MemoryAccessError::HeapOutOfBounds);
+    }
+
+    #[test]
+    fn read_rejects_overflowing_offset_plus_len() {
+        let (store, memory) = test_memory();
+        let err = read(&memory, &store, u32::MAX, u32::MAX).unwrap_err();
+        assert_eq!(err, MemoryAccessError::Overflow);
+    }
+
+    #[test]
+    fn write_rejects_out_of_bounds_offset() {
+        let (mut store, memory) = test_memory();
+        let size = memory.data(&store).len() as u32;
+        let err = write(&memory, &mut store, size, &[1]).unwrap_err();
+        assert_eq!(err, MemoryAccessError::HeapOutOfBounds);
+    }
+
+    #[test]
+    fn write_rejects_overflowing_offset_plus_len() {
+        let (mut store, memory) = test_memory();
+        let err = write(&memory, &mut store, u32::MAX, &[1, 2, 3, 4]).unwrap_err();
+        assert_eq!(err, MemoryAccessError::Overflow);
+    }
+
+    #[test]
+    fn read_u32_round_trips_little_endian() {
+        let (mut store, memory) = test_memory();
+        write(&memory, &mut store, 0, &42u32.to_le_bytes()).unwrap();
+        assert_eq!(read_u32(&memory, &store, 0).unwrap(), 42);
+    }
+
+    #[test]
+    fn read_u32_rejects_out_of_bounds_offset() {
+        let (store, memory) = test_memory();
+        let size = memory.data(&store).len() as u32;
+        let err = read_u32(&memory, &store, size - 1).unwrap_err();
+        assert_eq!(err, MemoryAccessError::HeapOutOfBounds);
+    }
+}